@@ -1,14 +1,89 @@
 use crate::core::{AppError, AppResult, Secret};
+use crate::credentials;
+use crate::github_client::{GitHubAuth, OrgSecretVisibility, DEFAULT_MAX_RETRY_ATTEMPTS};
 use dotenv::dotenv;
+use secrecy::SecretString;
 use serde::Deserialize;
 use std::env;
 
+/// Which secrets endpoint a `Target` manages within its organization/repository.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetScope {
+    /// One repository's Actions secrets.
+    Repo,
+    /// An organization's secrets, visible to the repositories `visibility` allows.
+    Org(OrgSecretVisibility),
+    /// One deployment environment's secrets within a repository.
+    Environment(String),
+    /// A repository's Dependabot secrets.
+    Dependabot,
+}
+
+/// A single organization/repository (or org-wide) secrets sync destination.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Target {
+    pub organization: String,
+    /// `None` means this target manages org-level secrets rather than one
+    /// repository's Actions secrets.
+    pub repository: Option<String>,
+    /// Overrides `Config.secrets` for this target only, if present.
+    #[serde(default)]
+    pub secrets: Option<Vec<Secret>>,
+    /// Which secrets endpoint to target: `"repo"` (the default), `"org"`,
+    /// `"org:private"`, `"org:selected"`, `"dependabot"`, or
+    /// `"env:<environment-name>"`.
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl Target {
+    pub fn is_org_level(&self) -> bool {
+        self.repository.is_none()
+    }
+
+    /// Resolves `scope` into a `TargetScope`, falling back to `Org` or
+    /// `Repo` based on whether `repository` is set when `scope` is absent.
+    pub fn scope(&self) -> TargetScope {
+        match self.scope.as_deref() {
+            Some("repo") => TargetScope::Repo,
+            Some("org") => TargetScope::Org(OrgSecretVisibility::All),
+            Some("org:private") => TargetScope::Org(OrgSecretVisibility::Private),
+            Some("org:selected") => TargetScope::Org(OrgSecretVisibility::Selected),
+            Some("dependabot") => TargetScope::Dependabot,
+            Some(s) if s.starts_with("env:") => TargetScope::Environment(s["env:".len()..].to_string()),
+            Some(other) => {
+                eprintln!("Unrecognized target scope '{}', defaulting to repo/org based on 'repository'.", other);
+                self.default_scope()
+            }
+            None => self.default_scope(),
+        }
+    }
+
+    fn default_scope(&self) -> TargetScope {
+        if self.is_org_level() {
+            TargetScope::Org(OrgSecretVisibility::All)
+        } else {
+            TargetScope::Repo
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub organization: String,
     pub repository: String,
-    pub token: String,
+    pub token: SecretString,
     pub secrets: Vec<Secret>,
+    pub app_id: Option<u64>,
+    pub app_private_key: Option<SecretString>,
+    pub app_installation_id: Option<u64>,
+    /// Every org/repository/scope this run syncs. `GITHUB_TARGETS` can list
+    /// more than one organization, but `token`/`app_*` above resolve to a
+    /// single credential shared across all of them — see `github_auth`.
+    pub targets: Vec<Target>,
+    /// How many attempts `GitHubClient::send_with_retry` gets per request,
+    /// from `GITHUB_MAX_RETRY_ATTEMPTS` if set.
+    pub max_retry_attempts: u32,
 }
 
 impl Config {
@@ -19,18 +94,88 @@ impl Config {
             .map_err(|_| AppError::EnvVarNotFound("GITHUB_ORGANIZATION".to_string()))?;
         let repository = env::var("GITHUB_REPOSITORY")
             .map_err(|_| AppError::EnvVarNotFound("GITHUB_REPOSITORY".to_string()))?;
-        let token = env::var("GITHUB_TOKEN")
-            .map_err(|_| AppError::EnvVarNotFound("GITHUB_TOKEN".to_string()))?;
         let secrets_json = env::var("GITHUB_SECRETS")
             .map_err(|_| AppError::EnvVarNotFound("GITHUB_SECRETS".to_string()))?;
 
+        let app_id = env::var("GITHUB_APP_ID").ok().and_then(|v| v.parse().ok());
+        let app_private_key = env::var("GITHUB_APP_PRIVATE_KEY").ok().map(SecretString::from);
+        let app_installation_id = env::var("GITHUB_APP_INSTALLATION_ID")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        // A PAT is only needed when we're not authenticating as a GitHub App;
+        // otherwise it's resolved from env var -> keyring -> interactive prompt.
+        //
+        // This resolution only ever consults `organization`/`repository`
+        // above, even when `GITHUB_TARGETS` lists other organizations — there
+        // is no per-target credential lookup. Multi-org `GITHUB_TARGETS`
+        // therefore requires a single token (or GitHub App installation)
+        // that's valid across every listed organization; split credentials
+        // per org aren't supported today.
+        let token = if app_id.is_some() && app_private_key.is_some() && app_installation_id.is_some() {
+            SecretString::from(String::new())
+        } else {
+            credentials::resolve_token(&organization, &repository)?
+        };
+
         let secrets: Vec<Secret> = serde_json::from_str(&secrets_json)?;
 
+        let max_retry_attempts = match env::var("GITHUB_MAX_RETRY_ATTEMPTS").ok().and_then(|v| v.parse::<u32>().ok()) {
+            Some(0) => {
+                return Err(AppError::Unknown(
+                    "GITHUB_MAX_RETRY_ATTEMPTS must be at least 1 (0 would never send a request)".to_string(),
+                ))
+            }
+            Some(n) => n,
+            None => DEFAULT_MAX_RETRY_ATTEMPTS,
+        };
+
+        // Multiple orgs/repos (or org-wide secrets) can be listed in
+        // GITHUB_TARGETS; otherwise fall back to the single org/repo above.
+        let targets: Vec<Target> = match env::var("GITHUB_TARGETS") {
+            Ok(targets_json) => serde_json::from_str(&targets_json)?,
+            Err(_) => vec![Target {
+                organization: organization.clone(),
+                repository: Some(repository.clone()),
+                secrets: None,
+                scope: None,
+            }],
+        };
+
         Ok(Config {
             organization,
             repository,
             token,
             secrets,
+            app_id,
+            app_private_key,
+            app_installation_id,
+            targets,
+            max_retry_attempts,
         })
     }
+
+    /// The secrets to sync for `target`: its own override list if it has
+    /// one, otherwise the top-level `secrets`.
+    pub fn secrets_for<'a>(&'a self, target: &'a Target) -> &'a Vec<Secret> {
+        target.secrets.as_ref().unwrap_or(&self.secrets)
+    }
+
+    /// Builds the `GitHubAuth` this config describes, preferring a GitHub
+    /// App identity over a static token when both are present.
+    ///
+    /// This is the one credential used for every entry in `targets`. If
+    /// `GITHUB_TARGETS` spans multiple organizations, each of them must
+    /// accept this same token (or the same GitHub App installation) — there
+    /// is no way to give different targets different credentials.
+    pub fn github_auth(&self) -> GitHubAuth {
+        match (self.app_id, &self.app_private_key, self.app_installation_id) {
+            (Some(app_id), Some(private_key), Some(installation_id)) => GitHubAuth::App {
+                app_id,
+                private_key: private_key.clone(),
+                installation_id,
+            },
+            _ => GitHubAuth::Token(self.token.clone()),
+        }
+    }
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 use std::error::Error as StdError;
 use thiserror::Error;
@@ -9,10 +10,14 @@ pub enum SecretStatus {
     Deleted,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+// No `Serialize`: `SecretString` deliberately doesn't implement it (it only
+// unwraps for types marked `SerializableSecret`, and `String` isn't one), so
+// a `Secret` is never written back out as JSON/CBOR — only ever parsed in
+// from `GITHUB_SECRETS` or a target's `secrets` in `GITHUB_TARGETS`.
+#[derive(Deserialize, Clone, Debug)]
 pub struct Secret {
     pub name: String,
-    pub value: String,
+    pub value: SecretString,
     #[serde(skip_deserializing)]
     pub status: Option<SecretStatus>,
 }
@@ -20,18 +25,12 @@ pub struct Secret {
 #[derive(Clone)]
 pub struct SecretDetails {
     pub name: String,
-    pub value: String,
+    pub value: SecretString,
     pub created_at: String,
     pub updated_at: String,
     pub status: SecretStatus,
 }
 
-pub trait SecretsManager {
-    fn get_secrets(&self) -> &Vec<Secret>;
-    fn get_secret_details(&self, index: usize) -> Option<SecretDetails>;
-    fn manage_secrets(&self) -> Result<(), AppError>;
-}
-
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Environment variable not found: {0}")]
@@ -0,0 +1,69 @@
+use crate::core::{AppError, AppResult};
+use keyring::Entry;
+use secrecy::{ExposeSecret, SecretString};
+use std::io::{self, Write};
+
+const SERVICE_NAME: &str = "github-secrets-manager";
+
+fn entry(organization: &str, repository: &str) -> AppResult<Entry> {
+    let account = format!("{}/{}", organization, repository);
+    Entry::new(SERVICE_NAME, &account).map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+/// Resolves the GitHub token to use for `organization`/`repository`: an
+/// explicit `GITHUB_TOKEN` env var wins, then a previously-saved keyring
+/// entry, then an interactive prompt that offers to save what's entered to
+/// the keyring for next time.
+pub fn resolve_token(organization: &str, repository: &str) -> AppResult<SecretString> {
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        return Ok(SecretString::from(token));
+    }
+
+    let entry = entry(organization, repository)?;
+
+    if let Ok(token) = entry.get_password() {
+        return Ok(SecretString::from(token));
+    }
+
+    let token = prompt_for_token(organization, repository)?;
+
+    print!("Save this token to the system keyring for next time? [y/N] ");
+    io::stdout().flush().map_err(AppError::IoError)?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).map_err(AppError::IoError)?;
+
+    if answer.trim().eq_ignore_ascii_case("y") {
+        entry
+            .set_password(token.expose_secret())
+            .map_err(|e| AppError::Unknown(e.to_string()))?;
+    }
+
+    Ok(token)
+}
+
+fn prompt_for_token(organization: &str, repository: &str) -> AppResult<SecretString> {
+    print!("GitHub token for {}/{}: ", organization, repository);
+    io::stdout().flush().map_err(AppError::IoError)?;
+
+    let token = rpassword::read_password().map_err(AppError::IoError)?;
+    Ok(SecretString::from(token))
+}
+
+/// Removes the saved token for `organization`/`repository` from the system
+/// keyring, if one exists. Backs the `clear-credential` subcommand.
+pub fn clear_token(organization: &str, repository: &str) -> AppResult<()> {
+    let entry = entry(organization, repository)?;
+
+    match entry.delete_password() {
+        Ok(()) => {
+            println!("Cleared stored credential for {}/{}.", organization, repository);
+            Ok(())
+        }
+        Err(keyring::Error::NoEntry) => {
+            println!("No stored credential found for {}/{}.", organization, repository);
+            Ok(())
+        }
+        Err(e) => Err(AppError::Unknown(e.to_string())),
+    }
+}
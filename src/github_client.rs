@@ -1,6 +1,17 @@
-use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER, USER_AGENT};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::time::Duration as StdDuration;
+use tokio::sync::Mutex;
+
+/// How many times `send_with_retry` will attempt a request (including the
+/// first try) before giving up and surfacing the error, absent an explicit
+/// override. See `GitHubClient::with_auth`.
+pub const DEFAULT_MAX_RETRY_ATTEMPTS: u32 = 5;
 
 #[derive(Deserialize)]
 pub struct PublicKeyResponse {
@@ -24,67 +35,535 @@ struct UpdateSecretRequest {
     key_id: String,
 }
 
+/// Which repositories in an org can see an org-level secret. Mirrors the
+/// `visibility` field GitHub's org secrets API expects.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgSecretVisibility {
+    All,
+    Private,
+    Selected,
+}
+
+#[derive(Serialize)]
+struct UpdateOrgSecretRequest {
+    encrypted_value: String,
+    key_id: String,
+    visibility: OrgSecretVisibility,
+}
+
+/// How a `GitHubClient` authenticates its requests.
+#[derive(Clone)]
+pub enum GitHubAuth {
+    /// A static personal access token, used as-is on every request.
+    Token(SecretString),
+    /// A GitHub App identity, exchanged for a short-lived installation token.
+    App {
+        app_id: u64,
+        private_key: SecretString,
+        installation_id: u64,
+    },
+}
+
+#[derive(Serialize)]
+struct AppClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+#[derive(Deserialize)]
+struct InstallationTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+struct InstallationToken {
+    token: SecretString,
+    expires_at: DateTime<Utc>,
+}
+
 pub struct GitHubClient {
     client: reqwest::Client,
     organization: String,
     repository: String,
-    token: String,
+    auth: GitHubAuth,
+    installation_token: Mutex<Option<InstallationToken>>,
+    max_retry_attempts: u32,
 }
 
 impl GitHubClient {
     pub fn new(organization: &str, repository: &str, token: &str) -> Self {
+        Self::with_auth(
+            organization,
+            repository,
+            GitHubAuth::Token(SecretString::from(token.to_string())),
+        )
+    }
+
+    pub fn with_auth(organization: &str, repository: &str, auth: GitHubAuth) -> Self {
+        Self::with_auth_and_retries(organization, repository, auth, DEFAULT_MAX_RETRY_ATTEMPTS)
+    }
+
+    /// Like `with_auth`, but with an explicit cap on `send_with_retry`'s
+    /// attempts instead of `DEFAULT_MAX_RETRY_ATTEMPTS`. Used when
+    /// `GITHUB_MAX_RETRY_ATTEMPTS` overrides the default.
+    pub fn with_auth_and_retries(organization: &str, repository: &str, auth: GitHubAuth, max_retry_attempts: u32) -> Self {
         Self {
             client: reqwest::Client::new(),
             organization: organization.to_string(),
             repository: repository.to_string(),
-            token: token.to_string(),
+            auth,
+            installation_token: Mutex::new(None),
+            max_retry_attempts,
         }
     }
 
-    pub async fn get_public_key(&self) -> Result<PublicKeyResponse, Box<dyn Error>> {
+    /// Returns a valid bearer token, minting or refreshing a GitHub App
+    /// installation token if we're within ~60s of its expiry.
+    async fn bearer_token(&self) -> Result<SecretString, Box<dyn Error>> {
+        let (app_id, private_key, installation_id) = match &self.auth {
+            GitHubAuth::Token(token) => return Ok(token.clone()),
+            GitHubAuth::App {
+                app_id,
+                private_key,
+                installation_id,
+            } => (*app_id, private_key, *installation_id),
+        };
+
+        let mut guard = self.installation_token.lock().await;
+
+        let needs_refresh = match &*guard {
+            Some(existing) => existing.expires_at - Utc::now() < Duration::seconds(60),
+            None => true,
+        };
+
+        if needs_refresh {
+            let fresh = self
+                .mint_installation_token(app_id, private_key, installation_id)
+                .await?;
+            *guard = Some(fresh);
+        }
+
+        Ok(guard.as_ref().expect("installation token was just set").token.clone())
+    }
+
+    async fn mint_installation_token(
+        &self,
+        app_id: u64,
+        private_key: &SecretString,
+        installation_id: u64,
+    ) -> Result<InstallationToken, Box<dyn Error>> {
+        let jwt = self.sign_app_jwt(app_id, private_key)?;
+
         let url = format!(
-            "https://api.github.com/repos/{}/{}/actions/secrets/public-key",
-            self.organization, self.repository
+            "https://api.github.com/app/installations/{}/access_tokens",
+            installation_id
         );
 
         let response = self
             .client
-            .get(&url)
+            .post(&url)
             .header(USER_AGENT, "github-secrets-manager")
-            .header(AUTHORIZATION, format!("Bearer {}", self.token))
+            .header(AUTHORIZATION, format!("Bearer {}", jwt))
             .header(ACCEPT, "application/vnd.github.v3+json")
             .send()
             .await?;
 
         if response.status().is_success() {
-            let public_key = response.json::<PublicKeyResponse>().await?;
-            Ok(public_key)
+            let token = response.json::<InstallationTokenResponse>().await?;
+            Ok(InstallationToken {
+                token: SecretString::from(token.token),
+                expires_at: token.expires_at,
+            })
         } else {
             Err(Box::new(response.error_for_status().unwrap_err()))
         }
     }
 
-    pub async fn get_existing_secrets(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+    fn sign_app_jwt(&self, app_id: u64, private_key: &SecretString) -> Result<String, Box<dyn Error>> {
+        let now = Utc::now();
+        let claims = AppClaims {
+            iat: (now - Duration::seconds(60)).timestamp(),
+            exp: (now + Duration::seconds(600)).timestamp(),
+            iss: app_id.to_string(),
+        };
+
+        let key = EncodingKey::from_rsa_pem(private_key.expose_secret().as_bytes())?;
+        let token = encode(&Header::new(Algorithm::RS256), &claims, &key)?;
+
+        Ok(token)
+    }
+
+    /// Sends `request`, retrying on transient failures instead of treating
+    /// every non-success as terminal: a `403`/`429` with `Retry-After` or an
+    /// exhausted rate limit waits until the reset time, a `5xx` or connection
+    /// error backs off exponentially (1s, 2s, 4s, ...). Anything else is
+    /// surfaced immediately, same as before.
+    async fn send_with_retry(&self, request: RequestBuilder) -> Result<Response, Box<dyn Error>> {
+        for attempt in 1..=self.max_retry_attempts {
+            let attempt_request = request
+                .try_clone()
+                .expect("requests sent through send_with_retry must not use a streaming body");
+
+            let response = match attempt_request.send().await {
+                Ok(response) => response,
+                Err(_) if attempt < self.max_retry_attempts => {
+                    tokio::time::sleep(Self::backoff_delay(attempt)).await;
+                    continue;
+                }
+                Err(err) => return Err(Box::new(err)),
+            };
+
+            if response.status().is_success() {
+                return Ok(response);
+            }
+
+            if attempt < self.max_retry_attempts {
+                if let Some(delay) = Self::rate_limit_delay(&response) {
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+
+                if response.status().is_server_error() {
+                    tokio::time::sleep(Self::backoff_delay(attempt)).await;
+                    continue;
+                }
+            }
+
+            return Err(Box::new(response.error_for_status().unwrap_err()));
+        }
+
+        unreachable!("loop always returns by the final attempt")
+    }
+
+    /// Exponential backoff for the given 1-indexed attempt: 1s, 2s, 4s, ...
+    fn backoff_delay(attempt: u32) -> StdDuration {
+        StdDuration::from_secs(1u64 << (attempt - 1))
+    }
+
+    /// How long to wait before retrying a `403`/`429`, per `Retry-After` or
+    /// an exhausted `x-ratelimit-remaining` plus its `x-ratelimit-reset`
+    /// epoch. `None` means this wasn't a rate-limit response, or it was an
+    /// exhausted rate limit with no parseable `x-ratelimit-reset`.
+    fn rate_limit_delay(response: &Response) -> Option<StdDuration> {
+        if response.status() != StatusCode::FORBIDDEN && response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+
+        let headers = response.headers();
+
+        if let Some(retry_after) = headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            return Some(StdDuration::from_secs(retry_after));
+        }
+
+        let remaining = headers
+            .get("x-ratelimit-remaining")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<i64>().ok());
+
+        if remaining != Some(0) {
+            return None;
+        }
+
+        let reset_at = headers
+            .get("x-ratelimit-reset")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<i64>().ok())?;
+
+        let wait_seconds = (reset_at - Utc::now().timestamp()).max(1);
+        Some(StdDuration::from_secs(wait_seconds as u64))
+    }
+
+    pub async fn get_public_key(&self) -> Result<PublicKeyResponse, Box<dyn Error>> {
         let url = format!(
-            "https://api.github.com/repos/{}/{}/actions/secrets",
+            "https://api.github.com/repos/{}/{}/actions/secrets/public-key",
             self.organization, self.repository
         );
 
-        let response = self
+        let request = self
             .client
             .get(&url)
             .header(USER_AGENT, "github-secrets-manager")
-            .header(AUTHORIZATION, format!("Bearer {}", self.token))
-            .header(ACCEPT, "application/vnd.github.v3+json")
-            .send()
-            .await?;
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        let response = self.send_with_retry(request).await?;
+        let public_key = response.json::<PublicKeyResponse>().await?;
+        Ok(public_key)
+    }
+
+    pub async fn get_existing_secrets(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/actions/secrets?per_page=100",
+            self.organization, self.repository
+        );
+        self.fetch_paginated_secrets(url).await
+    }
+
+    /// Public (org-wide) secrets visible to every repository in the org
+    /// that's allowed to see them, fetched from `/orgs/{org}/actions/secrets`.
+    pub async fn get_org_existing_secrets(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/orgs/{}/actions/secrets?per_page=100",
+            self.organization
+        );
+        self.fetch_paginated_secrets(url).await
+    }
+
+    async fn fetch_paginated_secrets(&self, initial_url: String) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        let mut secrets = Vec::new();
+        let mut url = Some(initial_url);
+
+        while let Some(page_url) = url {
+            let request = self
+                .client
+                .get(&page_url)
+                .header(USER_AGENT, "github-secrets-manager")
+                .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+                .header(ACCEPT, "application/vnd.github.v3+json");
+
+            let response = self.send_with_retry(request).await?;
+
+            url = Self::next_page_url(response.headers());
 
-        if response.status().is_success() {
             let secret_list = response.json::<SecretListResponse>().await?;
-            Ok(secret_list.secrets)
-        } else {
-            Err(Box::new(response.error_for_status().unwrap_err()))
+            secrets.extend(secret_list.secrets);
         }
+
+        Ok(secrets)
+    }
+
+    pub async fn get_org_public_key(&self) -> Result<PublicKeyResponse, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/orgs/{}/actions/secrets/public-key",
+            self.organization
+        );
+
+        let request = self
+            .client
+            .get(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        let response = self.send_with_retry(request).await?;
+        let public_key = response.json::<PublicKeyResponse>().await?;
+        Ok(public_key)
+    }
+
+    pub async fn upsert_org_secret(
+        &self,
+        secret_name: &str,
+        encrypted_value: String,
+        key_id: String,
+        visibility: OrgSecretVisibility,
+    ) -> Result<(), Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/orgs/{}/actions/secrets/{}",
+            self.organization, secret_name
+        );
+
+        let update_secret_req = UpdateOrgSecretRequest {
+            encrypted_value,
+            key_id,
+            visibility,
+        };
+
+        let request = self
+            .client
+            .put(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json")
+            .header(CONTENT_TYPE, "application/json")
+            .json(&update_secret_req);
+
+        self.send_with_retry(request).await?;
+        println!("Org secret '{}' updated successfully!", secret_name);
+        Ok(())
+    }
+
+    pub async fn delete_org_secret(&self, secret_name: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/orgs/{}/actions/secrets/{}",
+            self.organization, secret_name
+        );
+
+        let request = self
+            .client
+            .delete(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        self.send_with_retry(request).await?;
+        println!("Org secret '{}' deleted successfully!", secret_name);
+        Ok(())
+    }
+
+    pub async fn get_environment_public_key(&self, environment_name: &str) -> Result<PublicKeyResponse, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/environments/{}/secrets/public-key",
+            self.organization, self.repository, environment_name
+        );
+
+        let request = self
+            .client
+            .get(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        let response = self.send_with_retry(request).await?;
+        let public_key = response.json::<PublicKeyResponse>().await?;
+        Ok(public_key)
+    }
+
+    pub async fn get_environment_existing_secrets(
+        &self,
+        environment_name: &str,
+    ) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/environments/{}/secrets?per_page=100",
+            self.organization, self.repository, environment_name
+        );
+        self.fetch_paginated_secrets(url).await
+    }
+
+    pub async fn upsert_environment_secret(
+        &self,
+        environment_name: &str,
+        secret_name: &str,
+        encrypted_value: String,
+        key_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/environments/{}/secrets/{}",
+            self.organization, self.repository, environment_name, secret_name
+        );
+
+        let update_secret_req = UpdateSecretRequest { encrypted_value, key_id };
+
+        let request = self
+            .client
+            .put(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json")
+            .header(CONTENT_TYPE, "application/json")
+            .json(&update_secret_req);
+
+        self.send_with_retry(request).await?;
+        println!("Environment secret '{}' updated successfully!", secret_name);
+        Ok(())
+    }
+
+    pub async fn delete_environment_secret(&self, environment_name: &str, secret_name: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/environments/{}/secrets/{}",
+            self.organization, self.repository, environment_name, secret_name
+        );
+
+        let request = self
+            .client
+            .delete(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        self.send_with_retry(request).await?;
+        println!("Environment secret '{}' deleted successfully!", secret_name);
+        Ok(())
+    }
+
+    pub async fn get_dependabot_public_key(&self) -> Result<PublicKeyResponse, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/dependabot/secrets/public-key",
+            self.organization, self.repository
+        );
+
+        let request = self
+            .client
+            .get(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        let response = self.send_with_retry(request).await?;
+        let public_key = response.json::<PublicKeyResponse>().await?;
+        Ok(public_key)
+    }
+
+    pub async fn get_dependabot_existing_secrets(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/dependabot/secrets?per_page=100",
+            self.organization, self.repository
+        );
+        self.fetch_paginated_secrets(url).await
+    }
+
+    pub async fn upsert_dependabot_secret(
+        &self,
+        secret_name: &str,
+        encrypted_value: String,
+        key_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/dependabot/secrets/{}",
+            self.organization, self.repository, secret_name
+        );
+
+        let update_secret_req = UpdateSecretRequest { encrypted_value, key_id };
+
+        let request = self
+            .client
+            .put(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json")
+            .header(CONTENT_TYPE, "application/json")
+            .json(&update_secret_req);
+
+        self.send_with_retry(request).await?;
+        println!("Dependabot secret '{}' updated successfully!", secret_name);
+        Ok(())
+    }
+
+    pub async fn delete_dependabot_secret(&self, secret_name: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/dependabot/secrets/{}",
+            self.organization, self.repository, secret_name
+        );
+
+        let request = self
+            .client
+            .delete(&url)
+            .header(USER_AGENT, "github-secrets-manager")
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
+
+        self.send_with_retry(request).await?;
+        println!("Dependabot secret '{}' deleted successfully!", secret_name);
+        Ok(())
+    }
+
+    /// Extracts the `rel="next"` URL from a paginated GitHub `Link` header,
+    /// if the response has another page.
+    fn next_page_url(headers: &reqwest::header::HeaderMap) -> Option<String> {
+        let link = headers.get("link")?.to_str().ok()?;
+
+        link.split(',').find_map(|part| {
+            let mut segments = part.split(';');
+            let url_segment = segments.next()?.trim();
+            let is_next = segments.any(|s| s.trim() == r#"rel="next""#);
+
+            is_next.then(|| url_segment.trim_start_matches('<').trim_end_matches('>').to_string())
+        })
     }
 
     pub async fn upsert_secret(
@@ -100,23 +579,18 @@ impl GitHubClient {
 
         let update_secret_req = UpdateSecretRequest { encrypted_value, key_id };
 
-        let response = self
+        let request = self
             .client
             .put(&url)
             .header(USER_AGENT, "github-secrets-manager")
-            .header(AUTHORIZATION, format!("Bearer {}", self.token))
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
             .header(ACCEPT, "application/vnd.github.v3+json")
             .header(CONTENT_TYPE, "application/json")
-            .json(&update_secret_req)
-            .send()
-            .await?;
+            .json(&update_secret_req);
 
-        if response.status().is_success() {
-            println!("Secret '{}' updated successfully!", secret_name);
-            Ok(())
-        } else {
-            Err(Box::new(response.error_for_status().unwrap_err()))
-        }
+        self.send_with_retry(request).await?;
+        println!("Secret '{}' updated successfully!", secret_name);
+        Ok(())
     }
 
     pub async fn delete_secret(&self, secret_name: &str) -> Result<(), Box<dyn Error>> {
@@ -125,20 +599,58 @@ impl GitHubClient {
             self.organization, self.repository, secret_name
         );
 
-        let response = self
+        let request = self
             .client
             .delete(&url)
             .header(USER_AGENT, "github-secrets-manager")
-            .header(AUTHORIZATION, format!("Bearer {}", self.token))
-            .header(ACCEPT, "application/vnd.github.v3+json")
-            .send()
-            .await?;
+            .header(AUTHORIZATION, format!("Bearer {}", self.bearer_token().await?.expose_secret()))
+            .header(ACCEPT, "application/vnd.github.v3+json");
 
-        if response.status().is_success() {
-            println!("Secret '{}' deleted successfully!", secret_name);
-            Ok(())
-        } else {
-            Err(Box::new(response.error_for_status().unwrap_err()))
-        }
+        self.send_with_retry(request).await?;
+        println!("Secret '{}' deleted successfully!", secret_name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    fn headers_with_link(link: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("link", link.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn finds_next_among_multiple_rel_values() {
+        let headers = headers_with_link(
+            r#"<https://api.github.com/resource?page=1>; rel="prev", <https://api.github.com/resource?page=3>; rel="next""#,
+        );
+
+        assert_eq!(
+            GitHubClient::next_page_url(&headers),
+            Some("https://api.github.com/resource?page=3".to_string())
+        );
+    }
+
+    #[test]
+    fn no_next_rel_returns_none() {
+        let headers = headers_with_link(r#"<https://api.github.com/resource?page=1>; rel="prev""#);
+
+        assert_eq!(GitHubClient::next_page_url(&headers), None);
+    }
+
+    #[test]
+    fn missing_link_header_returns_none() {
+        assert_eq!(GitHubClient::next_page_url(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn malformed_segment_without_rel_is_ignored() {
+        let headers = headers_with_link("<https://api.github.com/resource?page=3>");
+
+        assert_eq!(GitHubClient::next_page_url(&headers), None);
     }
 }
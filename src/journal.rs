@@ -0,0 +1,308 @@
+use crate::core::{AppError, AppResult};
+use base64::engine::general_purpose;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sodiumoxide::crypto::secretbox;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+const JOURNAL_KEYRING_SERVICE: &str = "github-secrets-manager-journal";
+
+/// Collapse the tail of the log into a fresh checkpoint once it grows past
+/// this many entries, so replay stays cheap no matter how long a target's
+/// been managed.
+const CHECKPOINT_THRESHOLD: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum JournalAction {
+    New,
+    Updated,
+    Deleted,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub name: String,
+    pub action: JournalAction,
+    pub value_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Checkpoint {
+    pub taken_at: Option<DateTime<Utc>>,
+    /// The last-known desired value hash for each secret, by name.
+    pub secrets: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct JournalState {
+    checkpoint: Checkpoint,
+    entries: Vec<JournalEntry>,
+}
+
+/// An append-only, encrypted-at-rest log of what a target's secrets were
+/// last set to, so `SecretsManager` can skip upserts whose value hasn't
+/// actually changed and the `rollback` subcommand can reconstruct an earlier desired
+/// state. Sealed with a key kept in the OS keyring.
+pub struct Journal {
+    path: PathBuf,
+    key: secretbox::Key,
+    state: JournalState,
+}
+
+/// Hashes a secret's plaintext value for journal comparisons.
+pub fn hash_value(value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(value.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+impl Journal {
+    pub fn open(scope_key: &str) -> AppResult<Self> {
+        let path = journal_path(scope_key);
+        let key = load_or_create_key(scope_key)?;
+
+        let state = if path.exists() {
+            let ciphertext = fs::read(&path).map_err(AppError::IoError)?;
+            decrypt_state(&ciphertext, &key)?
+        } else {
+            JournalState::default()
+        };
+
+        Ok(Self { path, key, state })
+    }
+
+    /// The desired state after replaying the checkpoint plus every entry:
+    /// secret name -> value hash, with deleted secrets absent.
+    pub fn current_desired_state(&self) -> HashMap<String, String> {
+        let mut desired = self.state.checkpoint.secrets.clone();
+
+        for entry in &self.state.entries {
+            match entry.action {
+                JournalAction::Deleted => {
+                    desired.remove(&entry.name);
+                }
+                JournalAction::New | JournalAction::Updated => {
+                    desired.insert(entry.name.clone(), entry.value_hash.clone());
+                }
+            }
+        }
+
+        desired
+    }
+
+    /// The desired state one step before the most recent change to each
+    /// secret, for the `rollback` subcommand to diff against the current state.
+    pub fn rollback_state(&self) -> HashMap<String, String> {
+        let mut desired = self.current_desired_state();
+        let mut undone = HashSet::new();
+
+        for (i, entry) in self.state.entries.iter().enumerate().rev() {
+            if !undone.insert(entry.name.clone()) {
+                continue;
+            }
+
+            // The secret's state just before this change: its previous
+            // entry in the log if it has one, otherwise the checkpoint.
+            let previous = self.state.entries[..i].iter().rev().find(|prev| prev.name == entry.name);
+            let previous_hash = match previous {
+                Some(prev) if prev.action == JournalAction::Deleted => None,
+                Some(prev) => Some(&prev.value_hash),
+                None => self.state.checkpoint.secrets.get(&entry.name),
+            };
+
+            match previous_hash {
+                Some(hash) => {
+                    desired.insert(entry.name.clone(), hash.clone());
+                }
+                None => {
+                    desired.remove(&entry.name);
+                }
+            }
+        }
+
+        desired
+    }
+
+    /// Records that `name` changed to `value_hash` (or was deleted), then
+    /// collapses the tail into a fresh checkpoint once it's grown too long.
+    pub fn record(&mut self, name: &str, action: JournalAction, value_hash: &str) -> AppResult<()> {
+        self.state.entries.push(JournalEntry {
+            timestamp: Utc::now(),
+            name: name.to_string(),
+            action,
+            value_hash: value_hash.to_string(),
+        });
+
+        if self.state.entries.len() > CHECKPOINT_THRESHOLD {
+            self.collapse();
+        }
+
+        self.save()
+    }
+
+    fn collapse(&mut self) {
+        let secrets = self.current_desired_state();
+        let taken_at = self.state.entries.last().map(|entry| entry.timestamp);
+
+        self.state.checkpoint = Checkpoint { taken_at, secrets };
+        self.state.entries.clear();
+    }
+
+    fn save(&self) -> AppResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(AppError::IoError)?;
+        }
+
+        let ciphertext = encrypt_state(&self.state, &self.key)?;
+        fs::write(&self.path, ciphertext).map_err(AppError::IoError)
+    }
+}
+
+fn journal_path(scope_key: &str) -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+
+    let file_name = scope_key.replace('/', "-");
+
+    PathBuf::from(home)
+        .join(".local/share/github-secrets-manager")
+        .join(format!("{}.journal", file_name))
+}
+
+fn load_or_create_key(scope_key: &str) -> AppResult<secretbox::Key> {
+    let entry = Entry::new(JOURNAL_KEYRING_SERVICE, scope_key).map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    if let Ok(encoded) = entry.get_password() {
+        let bytes = general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(AppError::Base64DecodeError)?;
+        return secretbox::Key::from_slice(&bytes).ok_or_else(|| AppError::Unknown("invalid journal key".to_string()));
+    }
+
+    let key = secretbox::gen_key();
+    entry
+        .set_password(&general_purpose::STANDARD.encode(key.as_ref()))
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    Ok(key)
+}
+
+fn encrypt_state(state: &JournalState, key: &secretbox::Key) -> AppResult<Vec<u8>> {
+    let plaintext = serde_cbor::to_vec(state).map_err(|e| AppError::Unknown(e.to_string()))?;
+    let nonce = secretbox::gen_nonce();
+    let ciphertext = secretbox::seal(&plaintext, &nonce, key);
+
+    let mut out = nonce.as_ref().to_vec();
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+fn decrypt_state(data: &[u8], key: &secretbox::Key) -> AppResult<JournalState> {
+    if data.len() < secretbox::NONCEBYTES {
+        return Err(AppError::Unknown("journal file is truncated".to_string()));
+    }
+
+    let (nonce_bytes, ciphertext) = data.split_at(secretbox::NONCEBYTES);
+    let nonce =
+        secretbox::Nonce::from_slice(nonce_bytes).ok_or_else(|| AppError::Unknown("invalid journal nonce".to_string()))?;
+
+    let plaintext =
+        secretbox::open(ciphertext, &nonce, key).map_err(|_| AppError::Unknown("failed to decrypt journal (wrong key?)".to_string()))?;
+
+    serde_cbor::from_slice(&plaintext).map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal_with(state: JournalState) -> Journal {
+        Journal {
+            path: PathBuf::new(),
+            key: secretbox::gen_key(),
+            state,
+        }
+    }
+
+    fn entry(name: &str, action: JournalAction, value_hash: &str) -> JournalEntry {
+        JournalEntry {
+            timestamp: Utc::now(),
+            name: name.to_string(),
+            action,
+            value_hash: value_hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trips() {
+        let key = secretbox::gen_key();
+        let mut state = JournalState::default();
+        state.entries.push(entry("FOO", JournalAction::New, "hash-foo"));
+
+        let ciphertext = encrypt_state(&state, &key).expect("encrypt");
+        let decrypted = decrypt_state(&ciphertext, &key).expect("decrypt");
+
+        assert_eq!(decrypted.entries.len(), 1);
+        assert_eq!(decrypted.entries[0].name, "FOO");
+        assert_eq!(decrypted.entries[0].value_hash, "hash-foo");
+    }
+
+    #[test]
+    fn decrypt_with_wrong_key_fails() {
+        let key = secretbox::gen_key();
+        let wrong_key = secretbox::gen_key();
+        let ciphertext = encrypt_state(&JournalState::default(), &key).expect("encrypt");
+
+        assert!(decrypt_state(&ciphertext, &wrong_key).is_err());
+    }
+
+    #[test]
+    fn current_desired_state_replays_checkpoint_and_entries() {
+        let mut state = JournalState::default();
+        state.checkpoint.secrets.insert("A".to_string(), "hash-a".to_string());
+        state.entries.push(entry("B", JournalAction::New, "hash-b"));
+        state.entries.push(entry("A", JournalAction::Deleted, ""));
+
+        let journal = journal_with(state);
+        let desired = journal.current_desired_state();
+
+        assert_eq!(desired.get("B"), Some(&"hash-b".to_string()));
+        assert_eq!(desired.get("A"), None);
+    }
+
+    #[test]
+    fn rollback_state_undoes_the_most_recent_change_per_secret() {
+        let mut state = JournalState::default();
+        state.checkpoint.secrets.insert("A".to_string(), "hash-0".to_string());
+        state.entries.push(entry("A", JournalAction::Updated, "hash-1"));
+        state.entries.push(entry("B", JournalAction::New, "hash-b"));
+
+        let journal = journal_with(state);
+        let rollback = journal.rollback_state();
+
+        // "A" existed before its most recent change, so rollback restores
+        // the checkpoint's value; "B" didn't exist before it was added, so
+        // rollback removes it entirely.
+        assert_eq!(rollback.get("A"), Some(&"hash-0".to_string()));
+        assert_eq!(rollback.get("B"), None);
+    }
+
+    #[test]
+    fn collapse_folds_entries_into_a_fresh_checkpoint() {
+        let mut state = JournalState::default();
+        state.entries.push(entry("A", JournalAction::New, "hash-a"));
+
+        let mut journal = journal_with(state);
+        journal.collapse();
+
+        assert!(journal.state.entries.is_empty());
+        assert_eq!(journal.state.checkpoint.secrets.get("A"), Some(&"hash-a".to_string()));
+    }
+}
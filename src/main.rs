@@ -1,13 +1,19 @@
 mod config;
 mod core;
+mod credentials;
 mod github_client;
+mod journal;
+mod runner;
+mod secrets_controller;
 mod secrets_manager;
 mod tui;
 
-use crate::config::Config;
-use crate::core::{AppResult, SecretsManager};
-use crate::github_client::GitHubClient;
-use crate::secrets_manager::GitHubSecretsManager;
+use crate::config::{Config, Target};
+use crate::core::{AppError, AppResult};
+use crate::github_client::{GitHubAuth, GitHubClient};
+use crate::journal::Journal;
+use crate::secrets_controller::RepoActionsController;
+use crate::secrets_manager::SecretsManager;
 use crate::tui::Tui;
 
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
@@ -15,19 +21,73 @@ use crossterm::execute;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
+use std::env;
+use std::error::Error;
 use std::io;
 use crossterm::terminal::{Clear, ClearType};
 use crossterm::cursor::MoveTo;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
+    // `clear-credential` removes a saved token from the keyring instead of
+    // running a sync; everything else falls through to the normal flow.
+    if env::args().nth(1).as_deref() == Some("clear-credential") {
+        let organization =
+            env::var("GITHUB_ORGANIZATION").map_err(|_| AppError::EnvVarNotFound("GITHUB_ORGANIZATION".to_string()))?;
+        let repository =
+            env::var("GITHUB_REPOSITORY").map_err(|_| AppError::EnvVarNotFound("GITHUB_REPOSITORY".to_string()))?;
+
+        return credentials::clear_token(&organization, &repository);
+    }
+
+    // `rollback` undoes the most recent journaled change to each secret in
+    // every configured target, skipping anything the journal can't
+    // reconstruct (it only ever stores value hashes, never the plaintext).
+    if env::args().nth(1).as_deref() == Some("rollback") {
+        return rollback().await;
+    }
+
     let config = Config::load()?;
-    let client = GitHubClient::new(&config.organization, &config.repository, &config.token);
 
-    let public_key = client.get_public_key().await?;
-    let existing_secrets = client.get_existing_secrets().await?;
+    // Multiple targets (or a single one that isn't a plain repo, e.g.
+    // org/environment/Dependabot scope) means this is a batch sync across
+    // scopes rather than one interactive session, so skip straight to the
+    // runner and print a summary instead of opening the TUI.
+    if config.targets.len() > 1 || config.targets.iter().any(|t| t.scope() != crate::config::TargetScope::Repo) {
+        let summaries = runner::sync_all(&config, config.github_auth()).await;
+
+        for summary in &summaries {
+            let label = label_for(&summary.organization, &summary.repository);
+
+            match &summary.outcome {
+                Ok(counts) => {
+                    println!(
+                        "{}: added {}, updated {}, deleted {}, failed {}",
+                        label, counts.added, counts.updated, counts.deleted, counts.failed
+                    );
+                    for error in &counts.errors {
+                        eprintln!("{}: {}", label, error);
+                    }
+                }
+                Err(err) => eprintln!("{}: failed - {}", label, err),
+            }
+        }
 
-    let secrets_manager = GitHubSecretsManager::new(config.secrets, existing_secrets, public_key, &client);
+        return Ok(());
+    }
+
+    // Exactly one plain-repo target: build the interactive session from
+    // *that* target, not the top-level organization/repository/secrets —
+    // those only coincide with it when GITHUB_TARGETS was never set.
+    let target = &config.targets[0];
+    let repository = target.repository.clone().unwrap_or_default();
+    let secrets = config.secrets_for(target).clone();
+
+    let client = GitHubClient::with_auth_and_retries(&target.organization, &repository, config.github_auth(), config.max_retry_attempts);
+    let controller = RepoActionsController::new(&client);
+    let journal = Journal::open(&format!("{}/{}", target.organization, repository))?;
+
+    let secrets_manager = SecretsManager::new(secrets, Box::new(controller), Some(journal)).await?;
 
     // Setup terminal
     enable_raw_mode()?;
@@ -40,9 +100,11 @@ async fn main() -> AppResult<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create TUI and run it
+    // Create TUI and run it. Applying changes now happens interactively
+    // from the TUI itself (the `a` keybinding), so there's nothing left to
+    // push once it exits.
     let mut tui = Tui::new(&secrets_manager);
-    let res = tui.run(&mut terminal);
+    let res = tui.run(&mut terminal).await;
 
     // Restore terminal
     disable_raw_mode()?;
@@ -53,8 +115,70 @@ async fn main() -> AppResult<()> {
         eprintln!("Error in TUI: {:?}", err);
     }
 
-    // Perform actual secret management after TUI closes
-    secrets_manager.manage_secrets()?;
+    Ok(())
+}
+
+/// Reverts every target in `config.targets` (not just the single legacy
+/// organization/repository) to the state its journal had before its most
+/// recent change. A failure on one target is reported and doesn't stop the
+/// rest, matching `runner::sync_all`'s non-fatal-per-target behavior.
+async fn rollback() -> AppResult<()> {
+    let config = Config::load()?;
+    let auth = config.github_auth();
+
+    for target in &config.targets {
+        if let Err(err) = rollback_target(target, auth.clone(), config.max_retry_attempts).await {
+            eprintln!("{}: rollback failed - {}", target_label(target), err);
+        }
+    }
 
     Ok(())
+}
+
+/// Reverts `target` to the state its journal had before its most recent
+/// change. Secrets added since then are deleted (that needs no prior
+/// value); secrets that were updated or deleted can only be flagged, since
+/// the journal never stores plaintext values.
+async fn rollback_target(target: &Target, auth: GitHubAuth, max_retry_attempts: u32) -> Result<(), Box<dyn Error>> {
+    let repository = target.repository.clone().unwrap_or_default();
+    let client = GitHubClient::with_auth_and_retries(&target.organization, &repository, auth, max_retry_attempts);
+    let (controller, scope_key) = runner::controller_for(target, &client);
+    let label = target_label(target);
+
+    let journal = Journal::open(&scope_key)?;
+    let current_state = journal.current_desired_state();
+    let target_state = journal.rollback_state();
+
+    for name in current_state.keys() {
+        if !target_state.contains_key(name) {
+            controller.delete(name).await?;
+            println!("{}: rolled back — deleted '{}' (added by its most recent change).", label, name);
+        }
+    }
+
+    for (name, hash) in &target_state {
+        let matches_target = current_state.get(name) == Some(hash);
+        if !matches_target {
+            println!(
+                "{}: cannot automatically restore '{}': its previous value isn't recoverable from the journal. \
+                 Re-supply it in GITHUB_SECRETS/GITHUB_TARGETS and run a normal sync if you need it back.",
+                label, name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn target_label(target: &Target) -> String {
+    label_for(&target.organization, &target.repository)
+}
+
+/// The human-readable label used in sync/rollback output for one org or
+/// org/repository scope.
+fn label_for(organization: &str, repository: &Option<String>) -> String {
+    match repository {
+        Some(repository) => format!("{}/{}", organization, repository),
+        None => format!("{} (org-level)", organization),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,99 @@
+use std::error::Error;
+
+use futures::stream::{self, StreamExt};
+
+use crate::config::{Config, Target, TargetScope};
+use crate::github_client::{GitHubAuth, GitHubClient};
+use crate::journal::Journal;
+use crate::secrets_controller::{
+    DependabotSecretsController, EnvironmentSecretsController, OrgSecretsController, RepoActionsController, SecretsController,
+};
+use crate::secrets_manager::{SecretsManager, SyncCounts};
+
+/// How many targets' pipelines run at once. Bounds the number of
+/// concurrent GitHub clients/journals rather than letting a large
+/// `GITHUB_TARGETS` list fan out unbounded.
+const MAX_CONCURRENT_TARGETS: usize = 5;
+
+/// The outcome of syncing one `Target`, for a consolidated end-of-run report.
+pub struct TargetSummary {
+    pub organization: String,
+    pub repository: Option<String>,
+    pub outcome: Result<SyncCounts, String>,
+}
+
+/// Syncs every target in `config.targets` concurrently (bounded by
+/// `MAX_CONCURRENT_TARGETS`), each against its own `GitHubClient` built
+/// from `auth`. A failing target doesn't stop the rest from running, and
+/// summaries are returned in completion order rather than `targets` order.
+pub async fn sync_all(config: &Config, auth: GitHubAuth) -> Vec<TargetSummary> {
+    stream::iter(config.targets.iter())
+        .map(|target| sync_target(config, target, auth.clone()))
+        .buffer_unordered(MAX_CONCURRENT_TARGETS)
+        .collect()
+        .await
+}
+
+async fn sync_target(config: &Config, target: &Target, auth: GitHubAuth) -> TargetSummary {
+    let repository = target.repository.clone().unwrap_or_default();
+    let client = GitHubClient::with_auth_and_retries(&target.organization, &repository, auth, config.max_retry_attempts);
+
+    let outcome = sync_one(config, target, &client).await.map_err(|e| e.to_string());
+
+    TargetSummary {
+        organization: target.organization.clone(),
+        repository: target.repository.clone(),
+        outcome,
+    }
+}
+
+/// Builds the right `SecretsController` for `target`'s scope, plus the
+/// journal scope key that keeps its change history separate from any
+/// other scope against the same organization/repository. Shared with
+/// `main::rollback` so every scope rolls back the same way it syncs.
+pub(crate) fn controller_for<'a>(target: &Target, client: &'a GitHubClient) -> (Box<dyn SecretsController + 'a>, String) {
+    let repository = target.repository.clone().unwrap_or_default();
+
+    match target.scope() {
+        TargetScope::Org(visibility) => (
+            Box::new(OrgSecretsController::new(client, visibility)),
+            target.organization.clone(),
+        ),
+        TargetScope::Repo => (
+            Box::new(RepoActionsController::new(client)),
+            format!("{}/{}", target.organization, repository),
+        ),
+        TargetScope::Environment(name) => {
+            let scope_key = format!("{}/{}/env:{}", target.organization, repository, name);
+            (Box::new(EnvironmentSecretsController::new(client, name)), scope_key)
+        }
+        TargetScope::Dependabot => (
+            Box::new(DependabotSecretsController::new(client)),
+            format!("{}/{}/dependabot", target.organization, repository),
+        ),
+    }
+}
+
+async fn sync_one(config: &Config, target: &Target, client: &GitHubClient) -> Result<SyncCounts, Box<dyn Error>> {
+    let secrets = config.secrets_for(target).clone();
+    let (controller, scope_key) = controller_for(target, client);
+    let journal = open_journal(&scope_key);
+
+    let manager = SecretsManager::new(secrets, controller, journal).await?;
+    Ok(manager.sync().await)
+}
+
+/// Opens `scope_key`'s journal, falling back to `None` (losing the
+/// skip-unchanged optimization and rollback history for this run) if it
+/// can't be opened. Unlike the single-target path, which treats this as
+/// fatal, a batch run keeps going — but the failure is still logged so it
+/// doesn't pass unnoticed.
+fn open_journal(scope_key: &str) -> Option<Journal> {
+    match Journal::open(scope_key) {
+        Ok(journal) => Some(journal),
+        Err(err) => {
+            eprintln!("{}: couldn't open journal ({}), continuing without it", scope_key, err);
+            None
+        }
+    }
+}
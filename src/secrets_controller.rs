@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use base64::engine::general_purpose;
+use base64::Engine;
+use secrecy::{ExposeSecret, SecretString};
+use sodiumoxide::crypto::{box_, sealedbox};
+use std::error::Error;
+use tokio::sync::OnceCell;
+
+use crate::core::AppError;
+use crate::github_client::{ExistingSecret, GitHubClient, OrgSecretVisibility, PublicKeyResponse};
+
+/// A single scope of GitHub secrets `SecretsManager` can target: one
+/// repository's Actions secrets, an org's secrets, one environment, or
+/// Dependabot secrets. Keeping this behind a trait means `SecretsManager`
+/// doesn't need to know which scope it's managing.
+#[async_trait]
+pub trait SecretsController: Send + Sync {
+    async fn list(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>>;
+    async fn ensure(&self, name: &str, value: &SecretString) -> Result<(), Box<dyn Error>>;
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>>;
+}
+
+/// Seals `value` for the given base64-encoded libsodium public key, ready to
+/// send as a secret's `encrypted_value`.
+fn seal_secret(value: &SecretString, public_key_b64: &str) -> Result<String, Box<dyn Error>> {
+    let public_key_bytes = general_purpose::STANDARD.decode(public_key_b64)?;
+    let pk = box_::PublicKey::from_slice(&public_key_bytes)
+        .ok_or_else(|| AppError::Unknown("GitHub returned a malformed public key".to_string()))?;
+    let sealed_box = sealedbox::seal(value.expose_secret().as_bytes(), &pk);
+    Ok(general_purpose::STANDARD.encode(&sealed_box))
+}
+
+/// A single repository's Actions secrets.
+pub struct RepoActionsController<'a> {
+    client: &'a GitHubClient,
+    public_key: OnceCell<PublicKeyResponse>,
+}
+
+impl<'a> RepoActionsController<'a> {
+    pub fn new(client: &'a GitHubClient) -> Self {
+        Self {
+            client,
+            public_key: OnceCell::new(),
+        }
+    }
+
+    async fn public_key(&self) -> Result<&PublicKeyResponse, Box<dyn Error>> {
+        self.public_key.get_or_try_init(|| self.client.get_public_key()).await
+    }
+}
+
+#[async_trait]
+impl<'a> SecretsController for RepoActionsController<'a> {
+    async fn list(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        self.client.get_existing_secrets().await
+    }
+
+    async fn ensure(&self, name: &str, value: &SecretString) -> Result<(), Box<dyn Error>> {
+        let public_key = self.public_key().await?;
+        let encrypted_value = seal_secret(value, &public_key.key)?;
+        self.client.upsert_secret(name, encrypted_value, public_key.key_id.clone()).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.client.delete_secret(name).await
+    }
+}
+
+/// An organization's secrets, visible to whichever repositories `visibility` allows.
+pub struct OrgSecretsController<'a> {
+    client: &'a GitHubClient,
+    visibility: OrgSecretVisibility,
+    public_key: OnceCell<PublicKeyResponse>,
+}
+
+impl<'a> OrgSecretsController<'a> {
+    pub fn new(client: &'a GitHubClient, visibility: OrgSecretVisibility) -> Self {
+        Self {
+            client,
+            visibility,
+            public_key: OnceCell::new(),
+        }
+    }
+
+    async fn public_key(&self) -> Result<&PublicKeyResponse, Box<dyn Error>> {
+        self.public_key.get_or_try_init(|| self.client.get_org_public_key()).await
+    }
+}
+
+#[async_trait]
+impl<'a> SecretsController for OrgSecretsController<'a> {
+    async fn list(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        self.client.get_org_existing_secrets().await
+    }
+
+    async fn ensure(&self, name: &str, value: &SecretString) -> Result<(), Box<dyn Error>> {
+        let public_key = self.public_key().await?;
+        let encrypted_value = seal_secret(value, &public_key.key)?;
+        self.client
+            .upsert_org_secret(name, encrypted_value, public_key.key_id.clone(), self.visibility)
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.client.delete_org_secret(name).await
+    }
+}
+
+/// One deployment environment's secrets within a repository.
+pub struct EnvironmentSecretsController<'a> {
+    client: &'a GitHubClient,
+    environment_name: String,
+    public_key: OnceCell<PublicKeyResponse>,
+}
+
+impl<'a> EnvironmentSecretsController<'a> {
+    pub fn new(client: &'a GitHubClient, environment_name: impl Into<String>) -> Self {
+        Self {
+            client,
+            environment_name: environment_name.into(),
+            public_key: OnceCell::new(),
+        }
+    }
+
+    async fn public_key(&self) -> Result<&PublicKeyResponse, Box<dyn Error>> {
+        self.public_key
+            .get_or_try_init(|| self.client.get_environment_public_key(&self.environment_name))
+            .await
+    }
+}
+
+#[async_trait]
+impl<'a> SecretsController for EnvironmentSecretsController<'a> {
+    async fn list(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        self.client.get_environment_existing_secrets(&self.environment_name).await
+    }
+
+    async fn ensure(&self, name: &str, value: &SecretString) -> Result<(), Box<dyn Error>> {
+        let public_key = self.public_key().await?;
+        let encrypted_value = seal_secret(value, &public_key.key)?;
+        self.client
+            .upsert_environment_secret(&self.environment_name, name, encrypted_value, public_key.key_id.clone())
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.client.delete_environment_secret(&self.environment_name, name).await
+    }
+}
+
+/// A repository's Dependabot secrets.
+pub struct DependabotSecretsController<'a> {
+    client: &'a GitHubClient,
+    public_key: OnceCell<PublicKeyResponse>,
+}
+
+impl<'a> DependabotSecretsController<'a> {
+    pub fn new(client: &'a GitHubClient) -> Self {
+        Self {
+            client,
+            public_key: OnceCell::new(),
+        }
+    }
+
+    async fn public_key(&self) -> Result<&PublicKeyResponse, Box<dyn Error>> {
+        self.public_key.get_or_try_init(|| self.client.get_dependabot_public_key()).await
+    }
+}
+
+#[async_trait]
+impl<'a> SecretsController for DependabotSecretsController<'a> {
+    async fn list(&self) -> Result<Vec<ExistingSecret>, Box<dyn Error>> {
+        self.client.get_dependabot_existing_secrets().await
+    }
+
+    async fn ensure(&self, name: &str, value: &SecretString) -> Result<(), Box<dyn Error>> {
+        let public_key = self.public_key().await?;
+        let encrypted_value = seal_secret(value, &public_key.key)?;
+        self.client
+            .upsert_dependabot_secret(name, encrypted_value, public_key.key_id.clone())
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.client.delete_dependabot_secret(name).await
+    }
+}
@@ -1,57 +1,67 @@
-use base64::engine::general_purpose;
-use base64::Engine;
-use serde::{Deserialize, Serialize};
-use sodiumoxide::crypto::{box_, sealedbox};
+use secrecy::{ExposeSecret, SecretString};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
 
-use crate::github_client::{ExistingSecret, GitHubClient, PublicKeyResponse};
+use crate::github_client::ExistingSecret;
+use crate::journal::{hash_value, Journal, JournalAction};
+use crate::secrets_controller::SecretsController;
 use std::error::Error;
 
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
-pub enum SecretStatus {
-    New,
-    Existing,
-    Deleted,
-}
+// `Secret`/`SecretStatus`/`SecretDetails` live in `core` so `Config` and
+// `SecretsManager` always agree on the same types instead of keeping
+// parallel, incompatible copies.
+pub use crate::core::{Secret, SecretDetails, SecretStatus};
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct Secret {
-    pub name: String,
-    pub value: String,
-    #[serde(skip_deserializing)]
-    pub status: Option<SecretStatus>,
+/// Progress reported while `SecretsManager::apply_with_progress` pushes
+/// pending changes to GitHub, so a driver (e.g. the TUI) can render it.
+pub enum ApplyEvent {
+    Progress { index: usize, total: usize, name: String },
+    Result { name: String, success: bool, error: Option<String> },
+    Finished,
 }
 
-#[derive(Clone)]
-pub struct SecretDetails {
-    pub name: String,
-    pub value: String,
-    pub created_at: String,
-    pub updated_at: String,
-    pub status: SecretStatus,
+/// How many secrets `SecretsManager::sync` actually added, updated, or
+/// deleted, plus how many of those operations failed, for a consolidated
+/// end-of-run report across targets. `errors` holds one `"name: message"`
+/// entry per failed secret, in the order they failed.
+#[derive(Debug, Default, Clone)]
+pub struct SyncCounts {
+    pub added: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
 }
 
 pub struct SecretsManager<'a> {
     secrets: Vec<Secret>,
     existing_secrets: Vec<ExistingSecret>,
-    public_key: PublicKeyResponse,
-    client: &'a GitHubClient,
+    controller: Box<dyn SecretsController + 'a>,
+    /// When present, lets `sync`/`apply_with_progress` skip upserts whose
+    /// value hash hasn't changed since the last run, and records what
+    /// actually changed.
+    journal: Option<Mutex<Journal>>,
 }
 
 impl<'a> SecretsManager<'a> {
-    pub fn new(
+    /// Fetches the controller's current secrets and wraps them together
+    /// with the desired `secrets` list, so the TUI and the runner work the
+    /// same regardless of which scope `controller` targets.
+    pub async fn new(
         mut secrets: Vec<Secret>,
-        existing_secrets: Vec<ExistingSecret>,
-        public_key: PublicKeyResponse,
-        client: &'a GitHubClient,
-    ) -> Self {
+        controller: Box<dyn SecretsController + 'a>,
+        journal: Option<Journal>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let existing_secrets = controller.list().await?;
+
         let mut manager = Self {
             secrets,
             existing_secrets,
-            public_key,
-            client,
+            controller,
+            journal: journal.map(Mutex::new),
         };
         manager.update_secret_statuses();
-        manager
+        Ok(manager)
     }
 
     fn update_secret_statuses(&mut self) {
@@ -69,7 +79,7 @@ impl<'a> SecretsManager<'a> {
             if !self.secrets.iter().any(|s| s.name == existing.name) {
                 self.secrets.push(Secret {
                     name: existing.name.clone(),
-                    value: String::new(), // We don't know the value
+                    value: SecretString::from(String::new()), // We don't know the value
                     status: Some(SecretStatus::Deleted),
                 });
             }
@@ -86,7 +96,7 @@ impl<'a> SecretsManager<'a> {
             SecretDetails {
                 name: secret.name.clone(),
                 value: if secret.status == Some(SecretStatus::Deleted) {
-                    "Unknown (Deleted)".to_string()
+                    SecretString::from("Unknown (Deleted)".to_string())
                 } else {
                     secret.value.clone()
                 },
@@ -97,23 +107,144 @@ impl<'a> SecretsManager<'a> {
         })
     }
 
-    pub async fn manage_secrets(&self) -> Result<(), Box<dyn Error>> {
-        let pk = self.decode_public_key()?;
+    /// Runs the categorize/upsert/delete pipeline and reports how many
+    /// secrets were added, updated, or deleted, and how many of those
+    /// operations failed. A failure on one secret doesn't stop the rest,
+    /// so a single bad secret never aborts the whole target's sync.
+    pub async fn sync(&self) -> SyncCounts {
+        let (new_secrets, updated_secrets, secrets_to_delete) = self.categorize_secrets();
+        let updated_secrets = self.skip_unchanged(updated_secrets).await;
+
+        let mut counts = SyncCounts::default();
+
+        for secret in &new_secrets {
+            match self.controller.ensure(&secret.name, &secret.value).await {
+                Ok(()) => {
+                    self.record_change(secret, JournalAction::New).await;
+                    counts.added += 1;
+                }
+                Err(err) => {
+                    counts.failed += 1;
+                    counts.errors.push(format!("{}: {}", secret.name, err));
+                }
+            }
+        }
+
+        for secret in &updated_secrets {
+            match self.controller.ensure(&secret.name, &secret.value).await {
+                Ok(()) => {
+                    self.record_change(secret, JournalAction::Updated).await;
+                    counts.updated += 1;
+                }
+                Err(err) => {
+                    counts.failed += 1;
+                    counts.errors.push(format!("{}: {}", secret.name, err));
+                }
+            }
+        }
+
+        for secret_name in secrets_to_delete {
+            match self.controller.delete(secret_name).await {
+                Ok(()) => {
+                    self.record_delete(secret_name).await;
+                    counts.deleted += 1;
+                }
+                Err(err) => {
+                    counts.failed += 1;
+                    counts.errors.push(format!("{}: {}", secret_name, err));
+                }
+            }
+        }
+
+        counts
+    }
 
+    /// Like `sync`, but reports per-secret progress and results on `tx` as
+    /// it goes instead of returning a summary at the end, so a driver
+    /// (e.g. the TUI) can render live progress.
+    pub async fn apply_with_progress(&self, tx: UnboundedSender<ApplyEvent>) -> Result<(), Box<dyn Error>> {
         let (new_secrets, updated_secrets, secrets_to_delete) = self.categorize_secrets();
+        let updated_secrets = self.skip_unchanged(updated_secrets).await;
 
-        self.print_secrets_to_manage(&new_secrets, &updated_secrets, &secrets_to_delete);
+        let total = new_secrets.len() + updated_secrets.len() + secrets_to_delete.len();
+        let mut index = 0;
 
-        self.upsert_secrets(&pk, &new_secrets, &updated_secrets).await?;
-        self.delete_secrets(secrets_to_delete).await?;
+        for (secret, action) in new_secrets
+            .iter()
+            .map(|secret| (secret, JournalAction::New))
+            .chain(updated_secrets.iter().map(|secret| (secret, JournalAction::Updated)))
+        {
+            index += 1;
+            let _ = tx.send(ApplyEvent::Progress {
+                index,
+                total,
+                name: secret.name.clone(),
+            });
+
+            let result = self.controller.ensure(&secret.name, &secret.value).await;
+            if result.is_ok() {
+                self.record_change(secret, action).await;
+            }
+
+            let _ = tx.send(ApplyEvent::Result {
+                name: secret.name.clone(),
+                success: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+
+        for secret_name in secrets_to_delete {
+            index += 1;
+            let _ = tx.send(ApplyEvent::Progress {
+                index,
+                total,
+                name: secret_name.clone(),
+            });
+
+            let result = self.controller.delete(secret_name).await;
+            if result.is_ok() {
+                self.record_delete(secret_name).await;
+            }
+
+            let _ = tx.send(ApplyEvent::Result {
+                name: secret_name.clone(),
+                success: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+
+        let _ = tx.send(ApplyEvent::Finished);
 
         Ok(())
     }
 
-    fn decode_public_key(&self) -> Result<box_::PublicKey, Box<dyn Error>> {
-        let public_key_bytes = general_purpose::STANDARD.decode(&self.public_key.key)?;
-        let pk = box_::PublicKey::from_slice(&public_key_bytes).unwrap();
-        Ok(pk)
+    /// Drops any `updated_secrets` whose value hash already matches the
+    /// journal's recorded state, so a re-run doesn't redundantly re-upsert
+    /// secrets that haven't actually changed.
+    async fn skip_unchanged<'s>(&self, updated_secrets: Vec<&'s Secret>) -> Vec<&'s Secret> {
+        let Some(journal) = &self.journal else {
+            return updated_secrets;
+        };
+
+        let desired = journal.lock().await.current_desired_state();
+
+        updated_secrets
+            .into_iter()
+            .filter(|secret| desired.get(&secret.name) != Some(&hash_value(secret.value.expose_secret())))
+            .collect()
+    }
+
+    async fn record_change(&self, secret: &Secret, action: JournalAction) {
+        if let Some(journal) = &self.journal {
+            let hash = hash_value(secret.value.expose_secret());
+            let _ = journal.lock().await.record(&secret.name, action, &hash);
+        }
+    }
+
+    async fn record_delete(&self, secret_name: &str) {
+        if let Some(journal) = &self.journal {
+            let _ = journal.lock().await.record(secret_name, JournalAction::Deleted, "");
+        }
     }
 
     fn categorize_secrets(
@@ -139,57 +270,4 @@ impl<'a> SecretsManager<'a> {
         (new_secrets, updated_secrets, secrets_to_delete)
     }
 
-    fn print_secrets_to_manage(
-        &self,
-        new_secrets: &Vec<&Secret>,
-        updated_secrets: &Vec<&Secret>,
-        secrets_to_delete: &Vec<&String>,
-    ) {
-        if !new_secrets.is_empty() {
-            println!("New secrets to be added:");
-            for secret in new_secrets {
-                println!("- {}", secret.name);
-            }
-        }
-
-        if !updated_secrets.is_empty() {
-            println!("Existing secrets to be updated:");
-            for secret in updated_secrets {
-                println!("- {}", secret.name);
-            }
-        }
-
-        if !secrets_to_delete.is_empty() {
-            println!("Secrets to be deleted:");
-            for secret_name in secrets_to_delete {
-                println!("- {}", secret_name);
-            }
-        }
-    }
-
-    async fn upsert_secrets(
-        &self,
-        pk: &box_::PublicKey,
-        new_secrets: &Vec<&Secret>,
-        updated_secrets: &Vec<&Secret>,
-    ) -> Result<(), Box<dyn Error>> {
-        for secret in new_secrets.iter().chain(updated_secrets.iter()) {
-            let sealed_box = sealedbox::seal(secret.value.as_bytes(), &pk);
-            let encrypted_value = general_purpose::STANDARD.encode(&sealed_box);
-
-            self.client
-                .upsert_secret(&secret.name, encrypted_value, self.public_key.key_id.clone())
-                .await?;
-        }
-
-        Ok(())
-    }
-
-    async fn delete_secrets(&self, secrets_to_delete: Vec<&String>) -> Result<(), Box<dyn Error>> {
-        for secret_name in secrets_to_delete {
-            self.client.delete_secret(secret_name).await?;
-        }
-
-        Ok(())
-    }
 }
\ No newline at end of file
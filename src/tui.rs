@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll, RawWaker, RawWakerVTable, Waker};
 use std::time::{Duration, Instant};
 use crossterm::event::{self, Event, KeyCode};
+use secrecy::ExposeSecret;
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -10,18 +15,108 @@ use ratatui::{
 };
 use std::io;
 use ratatui::layout::Margin;
-use crate::secrets_manager::{SecretsManager, SecretStatus};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use crate::secrets_manager::{ApplyEvent, SecretsManager, SecretStatus};
 
 enum NavDirection {
     Up,
     Down,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum PendingAction {
+    Quit,
+    Apply,
+}
+
+/// A no-op waker used to manually drive the apply future from the UI loop:
+/// we re-poll it every tick regardless of whether it "wakes" us, so we
+/// don't need a real reactor-integrated waker here.
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        raw_waker()
+    }
+    fn no_op(_: *const ()) {}
+    fn raw_waker() -> RawWaker {
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, no_op, no_op, no_op);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    unsafe { Waker::from_raw(raw_waker()) }
+}
+
 enum AppState {
     ListView,
     DetailsView,
 }
 
+/// Scores `candidate` against `query` as a case-insensitive subsequence
+/// match, preferring earlier and more contiguous hits, and reports the
+/// char indices of the matched characters for highlighting. Returns `None`
+/// if `query` isn't a subsequence of `candidate`.
+fn fuzzy_match(query: &str, candidate: &str) -> Option<(i32, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let query = query.to_lowercase();
+    let candidate_lower = candidate.to_lowercase();
+    let mut query_chars = query.chars();
+    let mut want = query_chars.next();
+
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut matched = Vec::new();
+
+    for (i, c) in candidate_lower.chars().enumerate() {
+        let Some(w) = want else { break };
+        if c == w {
+            score += match last_match {
+                Some(prev) if prev + 1 == i => 2, // contiguous match
+                _ => 1,
+            };
+            score -= i as i32; // earlier matches score higher
+            last_match = Some(i);
+            matched.push(i);
+            want = query_chars.next();
+        }
+    }
+
+    if want.is_some() {
+        None
+    } else {
+        Some((score, matched))
+    }
+}
+
+/// Splits `name` into spans, applying `base_style` throughout but marking
+/// the characters at `matched` (as returned by `fuzzy_match`) with an
+/// additional underline so a fuzzy-filtered row shows why it matched.
+fn highlight_matches(name: &str, matched: &[usize], base_style: Style) -> Vec<Span<'static>> {
+    if matched.is_empty() {
+        return vec![Span::styled(name.to_string(), base_style)];
+    }
+
+    let match_style = base_style.fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_matched = false;
+
+    for (i, c) in name.chars().enumerate() {
+        let is_matched = matched.contains(&i);
+        if !current.is_empty() && is_matched != current_matched {
+            spans.push(Span::styled(std::mem::take(&mut current), if current_matched { match_style } else { base_style }));
+        }
+        current.push(c);
+        current_matched = is_matched;
+    }
+
+    if !current.is_empty() {
+        spans.push(Span::styled(current, if current_matched { match_style } else { base_style }));
+    }
+
+    spans
+}
+
 pub struct StatusMessage {
     pub content: String,
     pub style: Style,
@@ -82,6 +177,14 @@ pub struct Tui<'a> {
     status_message: Option<StatusMessage>,
     color_scheme: ColorScheme,
     confirmation_dialog: Option<ConfirmationDialog>,
+    pending_action: PendingAction,
+    reveal_secret: bool,
+    filtering: bool,
+    filter_query: String,
+    applying: bool,
+    apply_future: Option<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+    apply_rx: Option<UnboundedReceiver<ApplyEvent>>,
+    apply_results: HashMap<String, bool>,
 }
 
 impl<'a> Tui<'a> {
@@ -93,71 +196,228 @@ impl<'a> Tui<'a> {
             status_message: None,
             color_scheme: ColorScheme::default(),
             confirmation_dialog: None,
+            pending_action: PendingAction::Quit,
+            reveal_secret: false,
+            filtering: false,
+            filter_query: String::new(),
+            applying: false,
+            apply_future: None,
+            apply_rx: None,
+            apply_results: HashMap::new(),
+        }
+    }
+
+    /// Indices into `secrets_manager.get_secrets()` that match the current
+    /// filter query, best match first, paired with the matched character
+    /// positions within each name (empty when there is no active filter).
+    /// Returns every index when there is no active filter.
+    fn scored_matches(&self) -> Vec<(usize, Vec<usize>)> {
+        if self.filter_query.is_empty() {
+            return (0..self.secrets_manager.get_secrets().len()).map(|i| (i, Vec::new())).collect();
         }
+
+        let mut scored: Vec<(usize, i32, Vec<usize>)> = self
+            .secrets_manager
+            .get_secrets()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| fuzzy_match(&self.filter_query, &s.name).map(|(score, positions)| (i, score, positions)))
+            .collect();
+
+        scored.sort_by(|a, b| b.1.cmp(&a.1));
+        scored.into_iter().map(|(i, _, positions)| (i, positions)).collect()
+    }
+
+    fn visible_indices(&self) -> Vec<usize> {
+        self.scored_matches().into_iter().map(|(i, _)| i).collect()
     }
 
-    pub fn run(&mut self, terminal: &mut Terminal<impl Backend>) -> io::Result<()> {
+    /// The underlying secret index the current selection points at.
+    fn selected_secret_index(&self) -> Option<usize> {
+        self.visible_indices().get(self.selected_index).copied()
+    }
+
+    fn clear_filter(&mut self) {
+        self.filtering = false;
+        self.filter_query.clear();
+        self.selected_index = 0;
+    }
+
+    pub async fn run(&mut self, terminal: &mut Terminal<impl Backend>) -> io::Result<()> {
         loop {
             terminal.draw(|f| self.ui(f))?;
 
-            if let Event::Key(key) = event::read()? {
-                match self.app_state {
-                    AppState::ListView => {
-                        match key.code {
-                            KeyCode::Char('q') => {
-                                self.show_confirmation_dialog(
-                                    "Are you sure you want to quit?".to_string(),
-                                    "Yes".to_string(),
-                                    "No".to_string(),
-                                );
+            self.drain_apply_events();
+            self.poll_apply_future();
+
+            // Bound how long we block on input so apply progress and status
+            // expiry keep getting a chance to run between keystrokes.
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    match self.app_state {
+                        AppState::ListView if self.filtering => match key.code {
+                            KeyCode::Esc => self.clear_filter(),
+                            KeyCode::Enter => {
+                                // Jump straight to the detail pane for the
+                                // top-scoring hit instead of just closing
+                                // the filter.
+                                self.filtering = false;
+                                self.selected_index = 0;
+                                if self.selected_secret_index().is_some() {
+                                    self.app_state = AppState::DetailsView;
+                                }
+                            }
+                            KeyCode::Backspace => {
+                                self.filter_query.pop();
+                                self.selected_index = 0;
+                            }
+                            KeyCode::Char(c) => {
+                                self.filter_query.push(c);
+                                self.selected_index = 0;
                             }
-                            KeyCode::Up => self.move_selection(NavDirection::Up),
-                            KeyCode::Down => self.move_selection(NavDirection::Down),
-                            KeyCode::Enter => self.toggle_view(),
                             _ => {}
+                        },
+                        AppState::ListView => {
+                            match key.code {
+                                KeyCode::Char('q') => {
+                                    self.show_confirmation_dialog(
+                                        PendingAction::Quit,
+                                        "Are you sure you want to quit?".to_string(),
+                                        "Yes".to_string(),
+                                        "No".to_string(),
+                                    );
+                                }
+                                KeyCode::Char('a') if !self.applying => {
+                                    self.show_confirmation_dialog(
+                                        PendingAction::Apply,
+                                        "Apply pending changes to GitHub?".to_string(),
+                                        "Yes".to_string(),
+                                        "No".to_string(),
+                                    );
+                                }
+                                KeyCode::Char('/') => self.filtering = true,
+                                KeyCode::Esc if !self.filter_query.is_empty() => self.clear_filter(),
+                                KeyCode::Up => self.move_selection(NavDirection::Up),
+                                KeyCode::Down => self.move_selection(NavDirection::Down),
+                                KeyCode::Enter => self.toggle_view(),
+                                _ => {}
+                            }
+                        }
+                        AppState::DetailsView => {
+                            match key.code {
+                                KeyCode::Enter => self.toggle_view(),
+                                KeyCode::Char('r') => self.reveal_secret = !self.reveal_secret,
+                                KeyCode::Char('q') => {
+                                    self.show_confirmation_dialog(
+                                        PendingAction::Quit,
+                                        "Are you sure you want to quit?".to_string(),
+                                        "Yes".to_string(),
+                                        "No".to_string(),
+                                    );
+                                }
+                                _ => {}
+                            }
                         }
                     }
-                    AppState::DetailsView => {
+
+                    if self.confirmation_dialog.is_some() {
                         match key.code {
-                            KeyCode::Enter => self.toggle_view(),
-                            KeyCode::Char('q') => {
-                                self.show_confirmation_dialog(
-                                    "Are you sure you want to quit?".to_string(),
-                                    "Yes".to_string(),
-                                    "No".to_string(),
-                                );
+                            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                                let action = self.pending_action;
+                                self.hide_confirmation_dialog();
+                                match action {
+                                    PendingAction::Quit => return Ok(()),
+                                    PendingAction::Apply => self.start_apply(),
+                                }
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') => {
+                                self.hide_confirmation_dialog();
                             }
                             _ => {}
                         }
                     }
                 }
+            }
 
-                if self.confirmation_dialog.is_some() {
-                    match key.code {
-                        KeyCode::Char('y') | KeyCode::Char('Y') => {
-                            self.hide_confirmation_dialog();
-                            return Ok(());
-                        }
-                        KeyCode::Char('n') | KeyCode::Char('N') => {
-                            self.hide_confirmation_dialog();
-                        }
-                        _ => {}
+            self.clear_expired_status_message();
+        }
+    }
+
+    /// Kicks off pushing pending changes to GitHub. The future lives on
+    /// `self` and is driven a step at a time from the main loop, so the UI
+    /// keeps redrawing and handling input while it runs.
+    fn start_apply(&mut self) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.apply_rx = Some(rx);
+        self.apply_results.clear();
+        self.applying = true;
+
+        let secrets_manager = self.secrets_manager;
+        self.apply_future = Some(Box::pin(async move {
+            let _ = secrets_manager.apply_with_progress(tx).await;
+        }));
+
+        self.set_status_message("Starting apply…".to_string(), Style::default().fg(Color::Yellow), None);
+    }
+
+    fn poll_apply_future(&mut self) {
+        if let Some(fut) = self.apply_future.as_mut() {
+            let waker = noop_waker();
+            let mut cx = TaskContext::from_waker(&waker);
+            if fut.as_mut().poll(&mut cx) == Poll::Ready(()) {
+                self.apply_future = None;
+            }
+        }
+    }
+
+    fn drain_apply_events(&mut self) {
+        let mut events = Vec::new();
+        if let Some(rx) = &mut self.apply_rx {
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+        }
+
+        for event in events {
+            match event {
+                ApplyEvent::Progress { index, total, name } => {
+                    self.set_status_message(
+                        format!("Applying {}/{}: {}", index, total, name),
+                        Style::default().fg(Color::Yellow),
+                        None,
+                    );
+                }
+                ApplyEvent::Result { name, success, error } => {
+                    if let Some(error) = error {
+                        self.set_status_message(
+                            format!("Failed to apply {}: {}", name, error),
+                            Style::default().fg(Color::Red),
+                            Some(Duration::from_secs(5)),
+                        );
                     }
+                    self.apply_results.insert(name, success);
+                }
+                ApplyEvent::Finished => {
+                    self.applying = false;
+                    self.apply_rx = None;
+                    self.set_status_message(
+                        "Apply finished".to_string(),
+                        Style::default().fg(Color::Green),
+                        Some(Duration::from_secs(5)),
+                    );
                 }
             }
-
-            self.clear_expired_status_message();
         }
     }
 
     fn move_selection(&mut self, direction: NavDirection) {
-        let secrets_len = self.secrets_manager.get_secrets().len();
+        let visible_len = self.visible_indices().len();
         match direction {
             NavDirection::Up => {
                 self.selected_index = self.selected_index.saturating_sub(1);
             }
             NavDirection::Down => {
-                if self.selected_index < secrets_len.saturating_sub(1) {
+                if self.selected_index < visible_len.saturating_sub(1) {
                     self.selected_index += 1;
                 }
             }
@@ -169,6 +429,7 @@ impl<'a> Tui<'a> {
             AppState::ListView => AppState::DetailsView,
             AppState::DetailsView => AppState::ListView,
         };
+        self.reveal_secret = false;
         self.set_status_message("View toggled".to_string(), Style::default().fg(Color::Yellow), Some(Duration::from_secs(3)));
     }
 
@@ -203,7 +464,12 @@ impl<'a> Tui<'a> {
             )
             .split(f.size());
 
-        let title = Paragraph::new("GitHub Secrets Manager")
+        let title = if self.filtering || !self.filter_query.is_empty() {
+            format!("GitHub Secrets Manager — Filter: {}", self.filter_query)
+        } else {
+            "GitHub Secrets Manager".to_string()
+        };
+        let title = Paragraph::new(title)
             .style(Style::default().fg(Color::Cyan))
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(title, chunks[0]);
@@ -214,8 +480,9 @@ impl<'a> Tui<'a> {
         }
 
         let footer = match self.app_state {
-            AppState::ListView => "↑↓: Navigate | Enter: View Details | q: Quit",
-            AppState::DetailsView => "Enter: Back to List | q: Quit",
+            AppState::ListView if self.filtering => "Esc: Clear Filter | Enter: View Top Match",
+            AppState::ListView => "↑↓: Navigate | Enter: View Details | /: Filter | a: Apply | q: Quit",
+            AppState::DetailsView => "Enter: Back to List | r: Reveal/Hide Value | q: Quit",
         };
 
         let footer = Paragraph::new(footer)
@@ -236,21 +503,24 @@ impl<'a> Tui<'a> {
     }
 
     fn render_secrets_list(&mut self, f: &mut Frame, area: Rect) {
+        let all_secrets = self.secrets_manager.get_secrets();
         let secrets: Vec<ListItem> = self
-            .secrets_manager
-            .get_secrets()
-            .iter()
-            .map(|s| {
-                let color = match s.status {
-                    Some(SecretStatus::New) => self.color_scheme.new,
-                    Some(SecretStatus::Existing) => self.color_scheme.existing,
-                    Some(SecretStatus::Deleted) => self.color_scheme.deleted,
-                    None => self.color_scheme.existing, // Default to existing if status is None
+            .scored_matches()
+            .into_iter()
+            .map(|(i, matched)| {
+                let s = &all_secrets[i];
+                let color = match self.apply_results.get(&s.name) {
+                    Some(true) => Color::Green,
+                    Some(false) => Color::Red,
+                    None => match s.status {
+                        Some(SecretStatus::New) => self.color_scheme.new,
+                        Some(SecretStatus::Existing) => self.color_scheme.existing,
+                        Some(SecretStatus::Deleted) => self.color_scheme.deleted,
+                        None => self.color_scheme.existing, // Default to existing if status is None
+                    },
                 };
-                ListItem::new(Span::styled(
-                    &s.name,
-                    Style::default().fg(color).add_modifier(Modifier::BOLD),
-                ))
+                let base_style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+                ListItem::new(Line::from(highlight_matches(&s.name, &matched, base_style)))
             })
             .collect();
 
@@ -265,7 +535,11 @@ impl<'a> Tui<'a> {
     }
 
     fn render_secret_details(&self, f: &mut Frame, area: Rect) {
-        if let Some(secret_details) = self.secrets_manager.get_secret_details(self.selected_index) {
+        let details = self
+            .selected_secret_index()
+            .and_then(|index| self.secrets_manager.get_secret_details(index));
+
+        if let Some(secret_details) = details {
             let status_color = match secret_details.status {
                 SecretStatus::New => self.color_scheme.new,
                 SecretStatus::Existing => self.color_scheme.existing,
@@ -279,7 +553,11 @@ impl<'a> Tui<'a> {
                 ]),
                 Line::from(vec![
                     Span::styled("Value: ", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(&secret_details.value),
+                    if self.reveal_secret {
+                        Span::raw(secret_details.value.expose_secret().to_string())
+                    } else {
+                        Span::raw("•".repeat(8))
+                    },
                 ]),
                 Line::from(vec![
                     Span::styled("Status: ", Style::default().add_modifier(Modifier::BOLD)),
@@ -381,11 +659,53 @@ impl<'a> Tui<'a> {
             .split(popup_layout[1])[1]
     }
 
-    pub fn show_confirmation_dialog(&mut self, message: String, yes_text: String, no_text: String) {
+    fn show_confirmation_dialog(&mut self, action: PendingAction, message: String, yes_text: String, no_text: String) {
+        self.pending_action = action;
         self.confirmation_dialog = Some(ConfirmationDialog::new(message, yes_text, no_text));
     }
 
-    pub fn hide_confirmation_dialog(&mut self) {
+    fn hide_confirmation_dialog(&mut self) {
         self.confirmation_dialog = None;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query_matches_everything_with_no_highlights() {
+        assert_eq!(fuzzy_match("", "anything"), Some((0, Vec::new())));
+    }
+
+    #[test]
+    fn non_subsequence_does_not_match() {
+        assert_eq!(fuzzy_match("xyz", "api_key"), None);
+    }
+
+    #[test]
+    fn match_is_case_insensitive() {
+        let (_, matched) = fuzzy_match("KEY", "api_key").expect("should match");
+        assert_eq!(matched, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn contiguous_match_scores_higher_than_scattered_at_the_same_start() {
+        let (contiguous, _) = fuzzy_match("key", "keyxx").expect("should match");
+        let (scattered, _) = fuzzy_match("key", "kxexy").expect("should match");
+        assert!(contiguous > scattered);
+    }
+
+    #[test]
+    fn earlier_match_scores_higher_than_later() {
+        let (earlier, _) = fuzzy_match("key", "key_other").expect("should match");
+        let (later, _) = fuzzy_match("key", "other_key").expect("should match");
+        assert!(earlier > later);
+    }
+
+    #[test]
+    fn matched_indices_point_at_the_matched_characters() {
+        let (_, matched) = fuzzy_match("ak", "api_key").expect("should match");
+        assert_eq!(matched, vec![0, 4]);
+    }
 }
\ No newline at end of file